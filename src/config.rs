@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::MAX_CACHE_SIZE;
+
+/// The time unit comparisons and bucket arithmetic are done in.
+///
+/// `Fill::time` carries sub-second resolution, but whole-second buckets
+/// can't distinguish fills that land in the same second on either side of
+/// a range boundary. Choosing a finer precision makes those comparisons
+/// exact at the cost of a smaller bucket width for the same `BUCKET_SIZE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePrecision {
+    Seconds,
+    Millis,
+    Micros,
+}
+
+impl TimePrecision {
+    /// Units of this precision per second, e.g. `1_000_000` for `Micros`.
+    pub fn units_per_second(self) -> i64 {
+        match self {
+            TimePrecision::Seconds => 1,
+            TimePrecision::Millis => 1_000,
+            TimePrecision::Micros => 1_000_000,
+        }
+    }
+
+    /// Render a timestamp in this precision's units.
+    pub fn timestamp(self, time: &DateTime<Utc>) -> i64 {
+        match self {
+            TimePrecision::Seconds => time.timestamp(),
+            TimePrecision::Millis => time.timestamp_millis(),
+            TimePrecision::Micros => time.timestamp_micros(),
+        }
+    }
+}
+
+/// Runtime configuration for a [`Processor`](crate::Processor).
+///
+/// Controls where the L2 [`BucketStore`](crate::store::BucketStore) persists
+/// its shard files, how the two cache tiers are sized, and the time
+/// precision/bucket granularity queries are evaluated at.
+#[derive(Debug, Clone)]
+pub struct ProcessorConfig {
+    /// Directory the disk-backed L2 store keeps its shard files in.
+    pub cache_dir: PathBuf,
+    /// Number of on-disk shards. Must be a power of two.
+    pub num_shards: u32,
+    /// Capacity of the in-memory L1 LRU cache, in buckets.
+    pub l1_capacity: usize,
+    /// Width of a bucket, in seconds.
+    pub bucket_duration_seconds: i64,
+    /// Unit that bucket arithmetic and range comparisons are done in.
+    pub precision: TimePrecision,
+    /// Number of retries attempted per API fetch before giving up on it
+    /// (so up to `retry_count + 1` total attempts).
+    pub retry_count: u32,
+    /// Base delay for the exponential backoff between retries. Actual
+    /// delay is `retry_base_delay * 2^attempt`, randomized by jitter.
+    pub retry_base_delay: Duration,
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        ProcessorConfig {
+            cache_dir: PathBuf::from("./bucket_cache"),
+            num_shards: 16,
+            l1_capacity: MAX_CACHE_SIZE,
+            bucket_duration_seconds: 3600,
+            precision: TimePrecision::Seconds,
+            retry_count: 3,
+            retry_base_delay: Duration::from_millis(100),
+        }
+    }
+}
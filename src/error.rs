@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// Error returned when fetching fills for a bucket run exhausts its
+/// retries. Carries enough instrumentation context to diagnose *which*
+/// fetch failed without re-deriving it from logs.
+#[derive(Debug)]
+pub struct FillFetchError {
+    /// The `[start, end)` bucket run this fetch was attempting to cover.
+    pub run: (i64, i64),
+    /// Number of attempts made (including the first) before giving up.
+    pub attempts: u32,
+    /// The query line that triggered this fetch.
+    pub query_line: String,
+    source: anyhow::Error,
+}
+
+impl FillFetchError {
+    pub fn new(run: (i64, i64), attempts: u32, query_line: impl Into<String>, source: anyhow::Error) -> Self {
+        FillFetchError {
+            run,
+            attempts,
+            query_line: query_line.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for FillFetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to fetch fills for bucket run [{}, {}) after {} attempt(s) (query: \"{}\"): {}",
+            self.run.0, self.run.1, self.attempts, self.query_line, self.source
+        )
+    }
+}
+
+impl std::error::Error for FillFetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
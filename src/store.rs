@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use chrono::{TimeZone, Utc};
+use log::{debug, trace};
+
+use crate::server::Fill;
+
+// sequence_number(8) + time_micros(8) + direction(4) + price(16) + quantity(16)
+const FILL_RECORD_SIZE: usize = 8 + 8 + 4 + 16 + 16;
+
+/// Persistent second-tier cache for hour buckets of [`Fill`] data.
+///
+/// Sits behind the in-memory L1 LRU so a warm restart doesn't have to
+/// re-hit `get_fills_api` for buckets we've already fetched.
+pub trait BucketStore {
+    /// Look up the fills for a bucket, if it has been persisted.
+    fn get(&mut self, key: (i64, i64)) -> anyhow::Result<Option<Vec<Fill>>>;
+    /// Persist the fills for a bucket, overwriting any prior value.
+    fn put(&mut self, key: (i64, i64), fills: &[Fill]) -> anyhow::Result<()>;
+    /// Whether a bucket is already known to the store, without reading it.
+    fn contains(&self, key: (i64, i64)) -> bool;
+}
+
+/// Directory-backed [`BucketStore`].
+///
+/// Buckets are spread across `num_shards` append-only shard files (chosen
+/// as a power of two) by `(bucket_start / bucket_size) & (num_shards - 1)`.
+/// Each shard is a sequence of `(bucket_start, bucket_end, len, payload)`
+/// records, where `payload` is the length-prefixed, fixed-width encoding
+/// of the bucket's `Vec<Fill>`. At startup every shard is scanned once to
+/// rebuild an in-memory index of `(bucket_start, bucket_end) -> (shard,
+/// offset)` so later lookups seek straight to the record.
+pub struct DiskBucketStore {
+    dir: PathBuf,
+    num_shards: u32,
+    bucket_size: i64,
+    index: HashMap<(i64, i64), (u32, u64)>,
+}
+
+impl DiskBucketStore {
+    /// Open (creating if necessary) a disk-backed store rooted at `dir`,
+    /// and load its existing bucket keys into an index.
+    ///
+    /// `num_shards` must be a power of two.
+    pub fn open(dir: impl Into<PathBuf>, num_shards: u32, bucket_size: i64) -> anyhow::Result<Self> {
+        if num_shards == 0 || !num_shards.is_power_of_two() {
+            return Err(anyhow::anyhow!("num_shards must be a non-zero power of two, got {}", num_shards));
+        }
+
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create cache dir {:?}: {}", dir, e))?;
+
+        let mut store = DiskBucketStore {
+            dir,
+            num_shards,
+            bucket_size,
+            index: HashMap::new(),
+        };
+        store.rebuild_index()?;
+        Ok(store)
+    }
+
+    fn shard_path(&self, shard: u32) -> PathBuf {
+        self.dir.join(format!("shard_{:04}.bin", shard))
+    }
+
+    fn shard_for(&self, bucket_start: i64) -> u32 {
+        ((bucket_start / self.bucket_size) as u64 & (self.num_shards as u64 - 1)) as u32
+    }
+
+    // Scan every shard file front-to-back, remembering the offset of the
+    // last record seen for each key so later writes shadow earlier ones.
+    fn rebuild_index(&mut self) -> anyhow::Result<()> {
+        for shard in 0..self.num_shards {
+            let path = self.shard_path(shard);
+            if !path.exists() {
+                continue;
+            }
+            let mut file = File::open(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to open shard {:?}: {}", path, e))?;
+
+            let mut offset = 0u64;
+            loop {
+                let mut header = [0u8; 20]; // bucket_start(8) + bucket_end(8) + len(4)
+                match file.read_exact(&mut header) {
+                    Ok(()) => {}
+                    Err(_) => break, // clean EOF (or truncated tail record, treat as end)
+                }
+                let bucket_start = i64::from_le_bytes(header[0..8].try_into().unwrap());
+                let bucket_end = i64::from_le_bytes(header[8..16].try_into().unwrap());
+                let len = u32::from_le_bytes(header[16..20].try_into().unwrap());
+
+                self.index.insert((bucket_start, bucket_end), (shard, offset));
+
+                if file.seek(SeekFrom::Current(len as i64)).is_err() {
+                    break;
+                }
+                offset += 20 + len as u64;
+            }
+
+            trace!("Indexed shard {:?}", path);
+        }
+
+        debug!("Loaded {} bucket keys from disk into L2 index", self.index.len());
+        Ok(())
+    }
+
+    fn read_record(&self, shard: u32, offset: u64) -> anyhow::Result<Vec<Fill>> {
+        let path = self.shard_path(shard);
+        let mut file = File::open(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to open shard {:?}: {}", path, e))?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| anyhow::anyhow!("Failed to seek shard {:?}: {}", path, e))?;
+
+        let mut header = [0u8; 20];
+        file.read_exact(&mut header)
+            .map_err(|e| anyhow::anyhow!("Failed to read record header in {:?}: {}", path, e))?;
+        let len = u32::from_le_bytes(header[16..20].try_into().unwrap()) as usize;
+
+        let mut payload = vec![0u8; len];
+        file.read_exact(&mut payload)
+            .map_err(|e| anyhow::anyhow!("Failed to read record payload in {:?}: {}", path, e))?;
+
+        decode_fills(&payload)
+    }
+}
+
+impl BucketStore for DiskBucketStore {
+    fn get(&mut self, key: (i64, i64)) -> anyhow::Result<Option<Vec<Fill>>> {
+        match self.index.get(&key) {
+            Some(&(shard, offset)) => Ok(Some(self.read_record(shard, offset)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&mut self, key: (i64, i64), fills: &[Fill]) -> anyhow::Result<()> {
+        let shard = self.shard_for(key.0);
+        let path = self.shard_path(shard);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to open shard {:?}: {}", path, e))?;
+
+        let offset = file
+            .metadata()
+            .map_err(|e| anyhow::anyhow!("Failed to stat shard {:?}: {}", path, e))?
+            .len();
+
+        let payload = encode_fills(fills);
+        file.write_all(&key.0.to_le_bytes())?;
+        file.write_all(&key.1.to_le_bytes())?;
+        file.write_all(&(payload.len() as u32).to_le_bytes())?;
+        file.write_all(&payload)?;
+
+        self.index.insert(key, (shard, offset));
+        trace!("Wrote {} fills for bucket {:?} to shard {:?}", fills.len(), key, path);
+        Ok(())
+    }
+
+    fn contains(&self, key: (i64, i64)) -> bool {
+        self.index.contains_key(&key)
+    }
+}
+
+fn encode_fills(fills: &[Fill]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(fills.len() * FILL_RECORD_SIZE);
+    for fill in fills {
+        buf.extend_from_slice(&fill.sequence_number.to_le_bytes());
+        buf.extend_from_slice(&fill.time.timestamp_micros().to_le_bytes());
+        buf.extend_from_slice(&fill.direction.to_le_bytes());
+        buf.extend_from_slice(&fill.price.serialize());
+        buf.extend_from_slice(&fill.quantity.serialize());
+    }
+    buf
+}
+
+fn decode_fills(buf: &[u8]) -> anyhow::Result<Vec<Fill>> {
+    if buf.len() % FILL_RECORD_SIZE != 0 {
+        return Err(anyhow::anyhow!(
+            "Corrupt bucket record: {} bytes is not a multiple of {}",
+            buf.len(),
+            FILL_RECORD_SIZE
+        ));
+    }
+
+    let mut fills = Vec::with_capacity(buf.len() / FILL_RECORD_SIZE);
+    for chunk in buf.chunks_exact(FILL_RECORD_SIZE) {
+        let sequence_number = u64::from_le_bytes(chunk[0..8].try_into().unwrap());
+        let time_micros = i64::from_le_bytes(chunk[8..16].try_into().unwrap());
+        let direction = i32::from_le_bytes(chunk[16..20].try_into().unwrap());
+        let price = rust_decimal::Decimal::deserialize(chunk[20..36].try_into().unwrap());
+        let quantity = rust_decimal::Decimal::deserialize(chunk[36..52].try_into().unwrap());
+
+        let time = Utc
+            .timestamp_micros(time_micros)
+            .single()
+            .ok_or_else(|| anyhow::anyhow!("Invalid stored timestamp: {} micros", time_micros))?;
+
+        fills.push(Fill {
+            sequence_number,
+            time,
+            direction,
+            price,
+            quantity,
+        });
+    }
+    Ok(fills)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn test_dir() -> PathBuf {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("trades-proxy-server-store-test-{}-{}", std::process::id(), id));
+        dir
+    }
+
+    fn test_fill(sequence_number: u64, timestamp: i64, direction: i32, price: Decimal, quantity: Decimal) -> Fill {
+        Fill {
+            sequence_number,
+            time: Utc.timestamp_opt(timestamp, 0).unwrap(),
+            direction,
+            price,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let fills = vec![
+            test_fill(1, 1_700_000_000, 1, dec!(100.25), dec!(1.5)),
+            test_fill(2, 1_700_000_100, -1, dec!(99.75), dec!(2.0)),
+        ];
+        let decoded = decode_fills(&encode_fills(&fills)).unwrap();
+        assert_eq!(decoded, fills);
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_buffer() {
+        let fills = vec![test_fill(1, 1_700_000_000, 1, dec!(1), dec!(1))];
+        let mut payload = encode_fills(&fills);
+        payload.pop();
+        assert!(decode_fills(&payload).is_err());
+    }
+
+    #[test]
+    fn test_put_then_get_roundtrip() {
+        let dir = test_dir();
+        let mut store = DiskBucketStore::open(&dir, 4, 3600).unwrap();
+        let key = (3600, 7200);
+        let fills = vec![test_fill(1, 3700, 1, dec!(50), dec!(1))];
+
+        assert!(!store.contains(key));
+        store.put(key, &fills).unwrap();
+        assert!(store.contains(key));
+        assert_eq!(store.get(key).unwrap(), Some(fills));
+        assert_eq!(store.get((0, 3600)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_rebuilds_index_on_reopen() {
+        let dir = test_dir();
+        let key = (3600, 7200);
+        let fills = vec![test_fill(1, 3700, 1, dec!(50), dec!(1))];
+
+        {
+            let mut store = DiskBucketStore::open(&dir, 4, 3600).unwrap();
+            store.put(key, &fills).unwrap();
+        }
+
+        // A fresh store over the same directory should rebuild its index
+        // from the shard files rather than starting empty.
+        let mut reopened = DiskBucketStore::open(&dir, 4, 3600).unwrap();
+        assert!(reopened.contains(key));
+        assert_eq!(reopened.get(key).unwrap(), Some(fills));
+    }
+
+    #[test]
+    fn test_put_overwrites_prior_value_for_same_key() {
+        let dir = test_dir();
+        let mut store = DiskBucketStore::open(&dir, 4, 3600).unwrap();
+        let key = (3600, 7200);
+
+        store.put(key, &[test_fill(1, 3700, 1, dec!(50), dec!(1))]).unwrap();
+        let updated = vec![test_fill(2, 3800, -1, dec!(60), dec!(2))];
+        store.put(key, &updated).unwrap();
+
+        assert_eq!(store.get(key).unwrap(), Some(updated));
+    }
+}